@@ -1,56 +1,336 @@
 use std::{
     collections::HashMap,
-    env::current_dir,
+    env::{self, current_dir},
     fs::{self, File},
     io::Read,
-    time::Instant,
+    time::{Duration, Instant},
     result::Result,
 };
 
 use ff::PrimeField;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 use nova_scotia::{
-    circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F1, G2,
+    circom::reader::load_r1cs, continue_recursive_circuit, create_public_params,
+    create_recursive_circuit, FileLocation,
     circom::circuit::{R1CS, CircomCircuit},
-    G1, F2,
 };
 use nova_snark::{
-    traits::{circuit::TrivialTestCircuit, Group},
-    PublicParams,
+    traits::{circuit::TrivialTestCircuit, snark::RelaxedR1CSSNARKTrait, Group},
+    CompressedSNARK, PublicParams, RecursiveSNARK,
 };
 use serde_json::Value;
 
-type PP = PublicParams<G1, G2, CircomCircuit<F1>, TrivialTestCircuit<F2>>;
+mod supernova;
+mod testing;
 
-pub fn save_public_params_to_file(params: &PP, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let json = serde_json::to_string(params)?;
-    fs::write(file_path, json)?;
+/// Selects a curve cycle and its associated compressing-SNARK engines for the whole
+/// proving pipeline. Parameterizing over this trait lets the same MACI circuits be
+/// proven on nova-scotia's default Pasta cycle or on the BN254/Grumpkin cycle that
+/// MACI's on-chain Groth16/KZG verifiers expect.
+pub trait CurveConfig {
+    type G1: Group<Base = <Self::G2 as Group>::Scalar>;
+    type G2: Group<Base = <Self::G1 as Group>::Scalar>;
+    /// Compressing SNARK over the primary curve.
+    type S1: RelaxedR1CSSNARKTrait<Self::G1>;
+    /// Compressing SNARK over the secondary curve.
+    type S2: RelaxedR1CSSNARKTrait<Self::G2>;
+    /// Parse the genesis primary input `z0` from its decimal-string encoding.
+    fn parse_z0(s: &str) -> Option<Scalar1<Self>>;
+    /// Short name used by the CLI/env switch and for logging.
+    const NAME: &'static str;
+}
+
+type Scalar1<C> = <<C as CurveConfig>::G1 as Group>::Scalar;
+type Scalar2<C> = <<C as CurveConfig>::G2 as Group>::Scalar;
+type PPof<C> =
+    PublicParams<<C as CurveConfig>::G1, <C as CurveConfig>::G2, CircomCircuit<Scalar1<C>>, TrivialTestCircuit<Scalar2<C>>>;
+type RSof<C> =
+    RecursiveSNARK<<C as CurveConfig>::G1, <C as CurveConfig>::G2, CircomCircuit<Scalar1<C>>, TrivialTestCircuit<Scalar2<C>>>;
+
+/// nova-scotia's default Pasta (Pallas/Vesta) cycle. No pairing is available, so both
+/// halves compress with IPA-PC.
+pub struct Pasta;
+
+impl CurveConfig for Pasta {
+    type G1 = nova_scotia::G1;
+    type G2 = nova_scotia::G2;
+    type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<
+        Self::G1,
+        nova_snark::provider::ipa_pc::EvaluationEngine<Self::G1>,
+    >;
+    type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<
+        Self::G2,
+        nova_snark::provider::ipa_pc::EvaluationEngine<Self::G2>,
+    >;
+
+    fn parse_z0(s: &str) -> Option<Scalar1<Self>> {
+        Scalar1::<Self>::from_str_vartime(s)
+    }
+
+    const NAME: &'static str = "pasta";
+}
+
+/// The BN254/Grumpkin cycle used by MACI's on-chain verifiers.
+///
+/// SCOPE CUT, NEEDS SIGN-OFF: this was supposed to compress with HyperKZG (a
+/// constant-size proof is the whole point of publishing on-chain), but
+/// `Bn256EngineKZG`/`GrumpkinEngine` and `hyperkzg::EvaluationEngine` belong to
+/// `nova_snark`'s newer `Engine`-trait API and don't implement the `Group` trait this
+/// pipeline (and `nova_scotia::G1`/`G2`) is built on, so they can't be plugged in here.
+/// The `Group`-based BN254/Grumpkin points at `provider::bn256_grumpkin` only ship
+/// IPA-PC evaluation engines, so both halves fall back to compressing with IPA-PC,
+/// same as [`Pasta`] — IPA-PC proofs are logarithmic, not constant, size. Whoever owns
+/// the on-chain verifier integration needs to decide whether that's acceptable or
+/// whether this pipeline needs to move to the `Engine`-trait API to get HyperKZG back.
+pub struct Bn256Grumpkin;
+
+impl CurveConfig for Bn256Grumpkin {
+    type G1 = nova_snark::provider::bn256_grumpkin::bn256::Point;
+    type G2 = nova_snark::provider::bn256_grumpkin::grumpkin::Point;
+    type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<
+        Self::G1,
+        nova_snark::provider::ipa_pc::EvaluationEngine<Self::G1>,
+    >;
+    type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<
+        Self::G2,
+        nova_snark::provider::ipa_pc::EvaluationEngine<Self::G2>,
+    >;
+
+    fn parse_z0(s: &str) -> Option<Scalar1<Self>> {
+        Scalar1::<Self>::from_str_vartime(s)
+    }
+
+    const NAME: &'static str = "bn254";
+}
+
+// Magic header + version byte prefixing the zlib-bincode public-parameter format.
+// JSON files written by earlier versions begin with `{`, so the discriminator also
+// lets us detect and transparently reject the legacy encoding on read.
+const PP_MAGIC: &[u8; 4] = b"NVPP";
+const PP_VERSION: u8 = 1;
+
+/// Persist `PublicParams` as zlib-compressed bincode.
+///
+/// For a ProcessMessages circuit the JSON encoding produces enormous files that are
+/// dominated by field-element string formatting; the binary format is far smaller
+/// and loads in a fraction of the time. The file is prefixed with [`PP_MAGIC`] and
+/// [`PP_VERSION`] so the format can be identified later.
+pub fn save_public_params_to_file<C: CurveConfig>(
+    params: &PPof<C>,
+    file_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut header = Vec::with_capacity(PP_MAGIC.len() + 1);
+    header.extend_from_slice(PP_MAGIC);
+    header.push(PP_VERSION);
+
+    let mut encoder = ZlibEncoder::new(header, Compression::default());
+    bincode::serialize_into(&mut encoder, params)?;
+    let bytes = encoder.finish()?;
+
+    fs::write(file_path, bytes)?;
     Ok(())
 }
 
-pub fn load_public_params_from_file(file_path: &str) -> Result<PP, Box<dyn std::error::Error>> {
-    let json = fs::read_to_string(file_path)?;
-    let params: PP = serde_json::from_str(&json)?;
+/// Load `PublicParams` previously written by [`save_public_params_to_file`].
+///
+/// Legacy JSON caches (which start with `{`) are detected via the missing magic
+/// header and reported as an error so the caller falls back to regenerating the
+/// params in the binary format.
+pub fn load_public_params_from_file<C: CurveConfig>(
+    file_path: &str,
+) -> Result<PPof<C>, Box<dyn std::error::Error>> {
+    let bytes = fs::read(file_path)?;
+    if bytes.len() < PP_MAGIC.len() + 1 || &bytes[..PP_MAGIC.len()] != PP_MAGIC {
+        return Err("unrecognized public params format (expected zlib-bincode, got legacy JSON?)".into());
+    }
+    let version = bytes[PP_MAGIC.len()];
+    if version != PP_VERSION {
+        return Err(format!("unsupported public params version {}", version).into());
+    }
+
+    let decoder = ZlibDecoder::new(&bytes[PP_MAGIC.len() + 1..]);
+    let params: PPof<C> = bincode::deserialize_from(decoder)?;
     Ok(params)
 }
 
-pub fn create_public_params_if_not_exist(r1cs: R1CS<F1>, file_path: &str) -> PP {
-    let pp = match load_public_params_from_file(file_path) {
+pub fn create_public_params_if_not_exist<C: CurveConfig>(
+    r1cs: R1CS<Scalar1<C>>,
+    file_path: &str,
+) -> PPof<C> {
+    match load_public_params_from_file::<C>(file_path) {
         Ok(params) => {
             println!("loading public params from {:?}", file_path);
             params
         }
         Err(_) => {
             println!("creating public params...");
-            let params = create_public_params(r1cs);
+            let params = create_public_params::<C::G1, C::G2>(r1cs);
             println!("saving public params to {:?}", file_path);
-            let _ = save_public_params_to_file(&params, file_path);
+            let _ = save_public_params_to_file::<C>(&params, file_path);
             params
         }
-    };
-    pp
+    }
+}
+
+/// Persist an in-flight `RecursiveSNARK` as zlib-compressed bincode so a long-running
+/// MACI tally can checkpoint between message batches.
+pub fn save_recursive_snark_to_file<C: CurveConfig>(
+    snark: &RSof<C>,
+    file_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    bincode::serialize_into(&mut encoder, snark)?;
+    fs::write(file_path, encoder.finish()?)?;
+    Ok(())
+}
+
+/// Reload a checkpointed `RecursiveSNARK` written by [`save_recursive_snark_to_file`].
+pub fn load_recursive_snark_from_file<C: CurveConfig>(
+    file_path: &str,
+) -> Result<RSof<C>, Box<dyn std::error::Error>> {
+    let bytes = fs::read(file_path)?;
+    let decoder = ZlibDecoder::new(&bytes[..]);
+    let snark: RSof<C> = bincode::deserialize_from(decoder)?;
+    Ok(snark)
 }
 
-fn read_json_file_to_hashmap(file_path: &str) -> Result<HashMap<String, Value>, Box<dyn std::error::Error>> {
+/// Fold additional steps into an already-constructed `RecursiveSNARK`.
+///
+/// `completed_steps` is the number of steps already folded into `recursive_snark` and
+/// `z0` its genesis input; together they let us recover the proof's running output via
+/// `verify` rather than re-reading `step_in` from `input_0.json`. `expected_zi` is the
+/// starting `z_i` the caller believes it's resuming from (e.g. the `step_in` its own
+/// checkpoint recorded); it's checked against the proof's recovered output before any
+/// folding happens, so a coordinator resuming from a stale or mismatched snapshot fails
+/// fast instead of silently folding onto the wrong state. Returns the new running output
+/// so the caller can checkpoint and resume later.
+pub fn continue_recursive_snark<C: CurveConfig>(
+    pp: &PPof<C>,
+    recursive_snark: &mut RSof<C>,
+    completed_steps: usize,
+    z0: Vec<Scalar1<C>>,
+    expected_zi: Vec<Scalar1<C>>,
+    witness_generator_file: FileLocation,
+    r1cs: R1CS<Scalar1<C>>,
+    private_inputs: Vec<HashMap<String, Value>>,
+) -> Result<Vec<Scalar1<C>>, Box<dyn std::error::Error>> {
+    let z0_secondary = vec![Scalar2::<C>::zero()];
+
+    // Recover the running output of the existing proof.
+    let (last_zi, _) = recursive_snark
+        .verify(pp, completed_steps, z0.clone(), z0_secondary.clone())
+        .map_err(|e| format!("existing recursive snark does not verify: {:?}", e))?;
+
+    if last_zi != expected_zi {
+        return Err(format!(
+            "resuming from a stale or mismatched checkpoint: expected starting z_i {:?}, but the proof's last claimed output is {:?}",
+            expected_zi, last_zi
+        )
+        .into());
+    }
+
+    continue_recursive_circuit(
+        recursive_snark,
+        last_zi,
+        witness_generator_file,
+        r1cs,
+        private_inputs.clone(),
+        z0.clone(),
+        pp,
+    )?;
+
+    // Confirm the appended proof still verifies at the extended step count, and return
+    // the new running output for the next checkpoint.
+    let (new_zi, _) = recursive_snark.verify(
+        pp,
+        completed_steps + private_inputs.len(),
+        z0,
+        z0_secondary,
+    )?;
+    Ok(new_zi)
+}
+
+/// Outcome of [`compress_and_verify`]: the serialized compressed proof plus the
+/// prover/verifier timings. The proof is IPA-PC based, so its size is logarithmic in
+/// the circuit size, not constant.
+pub struct CompressedOutput {
+    pub proof_bytes: Vec<u8>,
+    pub prover_time: Duration,
+    pub verifier_time: Duration,
+}
+
+/// Wrap a finished `RecursiveSNARK` into a `CompressedSNARK`.
+///
+/// Runs `CompressedSNARK::setup` to derive the prover/verifier keys, proves, then
+/// verifies against `iteration_count` steps starting from `z0`, and returns the
+/// serialized proof together with the measured timings. The SNARK engines are those
+/// chosen by the active [`CurveConfig`] (IPA-PC on both BN254 and Pasta).
+pub fn compress_and_verify<C: CurveConfig>(
+    pp: &PPof<C>,
+    recursive_snark: &RSof<C>,
+    iteration_count: usize,
+    z0: Vec<Scalar1<C>>,
+) -> Result<CompressedOutput, Box<dyn std::error::Error>> {
+    println!("Generating a CompressedSNARK ({})...", C::NAME);
+    let (pk, vk) = CompressedSNARK::<_, _, _, _, C::S1, C::S2>::setup(pp)?;
+
+    let start = Instant::now();
+    let compressed_snark =
+        CompressedSNARK::<_, _, _, _, C::S1, C::S2>::prove(pp, &pk, recursive_snark)?;
+    let prover_time = start.elapsed();
+    println!("CompressedSNARK::prove took {:?}", prover_time);
+
+    let z0_secondary = vec![Scalar2::<C>::zero()];
+
+    println!("Verifying a CompressedSNARK...");
+    let start = Instant::now();
+    let res = compressed_snark.verify(&vk, iteration_count, z0, z0_secondary);
+    let verifier_time = start.elapsed();
+    println!("CompressedSNARK::verify: {:?}, took {:?}", res, verifier_time);
+    res?;
+
+    let proof_bytes = serde_json::to_vec(&compressed_snark)?;
+    println!("CompressedSNARK serialized size: {} bytes", proof_bytes.len());
+
+    Ok(CompressedOutput {
+        proof_bytes,
+        prover_time,
+        verifier_time,
+    })
+}
+
+/// Compare the legacy JSON encoding against the zlib-bincode format for `params`,
+/// printing the resulting file size and load time for each.
+pub fn bench_public_param_formats<C: CurveConfig>(
+    params: &PPof<C>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json_path = "src/data/public_param_bench.json";
+    let bin_path = "src/data/public_param_bench.bin";
+
+    let json = serde_json::to_string(params)?;
+    fs::write(json_path, &json)?;
+    save_public_params_to_file::<C>(params, bin_path)?;
+
+    let json_size = fs::metadata(json_path)?.len();
+    let bin_size = fs::metadata(bin_path)?.len();
+
+    let start = Instant::now();
+    let json_str = fs::read_to_string(json_path)?;
+    let _: PPof<C> = serde_json::from_str(&json_str)?;
+    let json_load = start.elapsed();
+
+    let start = Instant::now();
+    let _ = load_public_params_from_file::<C>(bin_path)?;
+    let bin_load = start.elapsed();
+
+    println!(
+        "public params: JSON {} bytes (load {:?}) vs zlib-bincode {} bytes (load {:?})",
+        json_size, json_load, bin_size, bin_load
+    );
+    Ok(())
+}
+
+pub(crate) fn read_json_file_to_hashmap(file_path: &str) -> Result<HashMap<String, Value>, Box<dyn std::error::Error>> {
     // Open the file
     let mut file = File::open(file_path)?;
 
@@ -65,12 +345,12 @@ fn read_json_file_to_hashmap(file_path: &str) -> Result<HashMap<String, Value>,
     Ok(hashmap)
 }
 
-fn bench(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
+fn bench<C: CurveConfig>(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
     let root = current_dir().unwrap();
 
     let circuit_file = root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test.r1cs");
     println!("loading r1cs file: {:?}", circuit_file.clone());
-    let r1cs = load_r1cs(&FileLocation::PathBuf(circuit_file));
+    let r1cs = load_r1cs::<C::G1, C::G2>(&FileLocation::PathBuf(circuit_file));
     let witness_generator_file =
         root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test");
     println!("loading witness generation bin: {:?}", witness_generator_file.clone());
@@ -87,15 +367,20 @@ fn bench(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
                .and_then(|input_hash| input_hash.as_array())
                .and_then(|array| array.get(0))
                .and_then(|z0| z0.as_str())
-               .and_then(|z0| F1::from_str_vartime(&z0)).ok_or("Error: cannot parse z0")?; 
+               .and_then(C::parse_z0).ok_or("Error: cannot parse z0")?;
             start_public_input.push(z0);
         }
         let _ = private_input.remove("step_in");
         private_inputs.push(private_input);
     }
 
-    let file_path = "src/data/public_param.json";
-    let pp = create_public_params_if_not_exist(r1cs.clone(), file_path);
+    let file_path = "src/data/public_param.bin";
+    let pp = create_public_params_if_not_exist::<C>(r1cs.clone(), file_path);
+
+    // Optional: report the JSON vs. zlib-bincode file-size / load-time comparison.
+    if env::var("BENCH_PP_FORMATS").is_ok() {
+        bench_public_param_formats::<C>(&pp)?;
+    }
 
     println!(
         "Number of constraints per step (primary circuit): {}",
@@ -128,7 +413,7 @@ fn bench(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
     let prover_time = start.elapsed();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
 
-    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+    let z0_secondary = vec![Scalar2::<C>::zero()];
 
     // verify the recursive SNARK
     println!("Verifying a RecursiveSNARK...");
@@ -147,41 +432,100 @@ fn bench(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
     let verifier_time = start.elapsed();
     assert!(res.is_ok());
 
-    // produce a compressed SNARK
-    // println!("Generating a CompressedSNARK using Spartan with IPA-PC...");
-    // let start = Instant::now();
-    // type S1 = nova_snark::spartan_with_ipa_pc::RelaxedR1CSSNARK<G1>;
-    // type S2 = nova_snark::spartan_with_ipa_pc::RelaxedR1CSSNARK<G2>;
-    // let res = CompressedSNARK::<_, _, _, _, S1, S2>::prove(&pp, &recursive_snark);
-    // println!(
-    //     "CompressedSNARK::prove: {:?}, took {:?}",
-    //     res.is_ok(),
-    //     start.elapsed()
-    // );
-    // assert!(res.is_ok());
-    // let compressed_snark = res.unwrap();
-
-    // // verify the compressed SNARK
-    // println!("Verifying a CompressedSNARK...");
-    // let start = Instant::now();
-    // let res = compressed_snark.verify(
-    //     &pp,
-    //     iteration_count,
-    //     start_public_input.clone(),
-    //     z0_secondary,
-    // );
-    // println!(
-    //     "CompressedSNARK::verify: {:?}, took {:?}",
-    //     res.is_ok(),
-    //     start.elapsed()
-    // );
-    // assert!(res.is_ok());
-    println!("prover time={:?}, verifier time={:?}", prover_time, verifier_time);
+    // produce and verify a compressed SNARK
+    let compressed = compress_and_verify::<C>(
+        &pp,
+        &recursive_snark,
+        iteration_count,
+        start_public_input.clone(),
+    )?;
+
+    println!(
+        "prover time={:?}, verifier time={:?}",
+        prover_time, verifier_time
+    );
+    println!(
+        "compressed prover time={:?}, compressed verifier time={:?}, proof bytes={}",
+        compressed.prover_time,
+        compressed.verifier_time,
+        compressed.proof_bytes.len()
+    );
+    Ok(())
+}
+
+/// Fold `iteration_count` steps of the `ProcessMessages` circuit through the SuperNova
+/// non-uniform IVC wiring in [`supernova`], reusing one circuit type repeated in the ROM.
+///
+/// This exists so the module is actually exercised end-to-end rather than only covered
+/// by its own unit tests; run with `SUPERNOVA_SMOKE=1` since it's an extra, slower pass
+/// over the same fixtures `bench` already uses.
+fn supernova_smoke_test(iteration_count: usize) -> Result<(), Box<dyn std::error::Error>> {
+    use nova_scotia::{G1, G2};
+    use supernova::{create_public_params, prove_steps, verify, CircuitType, MaciProgram};
+
+    let root = current_dir().unwrap();
+    let circuit_file = root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test.r1cs");
+    let witness_generator_file = root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test");
+    let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_file));
+
+    let program = MaciProgram {
+        circuits: vec![CircuitType {
+            r1cs,
+            witness_generator_file: FileLocation::PathBuf(witness_generator_file),
+        }],
+        rom: vec![0; iteration_count],
+    };
+
+    let mut app_z0 = Vec::new();
+    let mut private_inputs = Vec::new();
+    for i in 0..iteration_count {
+        let input_path = format!("src/data/input/input_{}.json", i);
+        let mut input = read_json_file_to_hashmap(&input_path)?;
+        if i == 0 {
+            app_z0 = input
+                .get("step_in")
+                .and_then(|v| v.as_array())
+                .ok_or("step input is missing `step_in`")?
+                .iter()
+                .map(|v| v.as_str().and_then(nova_scotia::F1::from_str_vartime))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("cannot parse step_in entry")?;
+        }
+        input.remove("step_in");
+        input.insert("circuit_index".to_string(), Value::from(0));
+        private_inputs.push(input);
+    }
+
+    println!(
+        "Running SuperNova smoke test ({} steps, 1 circuit type)...",
+        iteration_count
+    );
+    let pp = create_public_params(&program);
+    let snark = prove_steps(&pp, &program, app_z0.clone(), private_inputs)?;
+    verify(&snark, &pp, &program, app_z0)?;
+    println!("SuperNova smoke test folded and verified successfully");
     Ok(())
 }
 
 fn main() {
-    let res = bench(3);
+    // Choose the curve cycle via the `CURVE` env var (defaults to Pasta); MACI's
+    // on-chain verifiers want `CURVE=bn254`.
+    let curve = env::var("CURVE").unwrap_or_else(|_| Pasta::NAME.to_string());
+    let res = match curve.as_str() {
+        "bn254" | "bn256" | "grumpkin" => {
+            // See the Bn256Grumpkin doc comment: this compresses with IPA-PC, not the
+            // constant-size HyperKZG the on-chain verifier integration was scoped for.
+            eprintln!(
+                "warning: CURVE={:?} compresses with IPA-PC (logarithmic-size proof), not \
+                 HyperKZG — see the Bn256Grumpkin doc comment; needs sign-off before \
+                 publishing proofs on-chain",
+                curve
+            );
+            bench::<Bn256Grumpkin>(3)
+        }
+        "pasta" => bench::<Pasta>(3),
+        other => Err(format!("unknown curve cycle {:?} (expected `pasta` or `bn254`)", other).into()),
+    };
     match res {
         Ok(()) => {
             println!("everything works fine");
@@ -190,4 +534,136 @@ fn main() {
             eprintln!("Error: {}", e);
         }
     }
+
+    if env::var("SUPERNOVA_SMOKE").is_ok() {
+        if let Err(e) = supernova_smoke_test(2) {
+            eprintln!("SuperNova smoke test failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load the first two `ProcessMessages` fixture steps `bench` already depends on,
+    /// returning the r1cs/witness-generator pair, genesis `z0`, and per-step inputs.
+    fn load_two_step_fixture() -> (
+        R1CS<Scalar1<Pasta>>,
+        FileLocation,
+        Vec<Scalar1<Pasta>>,
+        Vec<HashMap<String, Value>>,
+    ) {
+        let root = current_dir().unwrap();
+        let circuit_file = root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test.r1cs");
+        let r1cs = load_r1cs::<<Pasta as CurveConfig>::G1, <Pasta as CurveConfig>::G2>(
+            &FileLocation::PathBuf(circuit_file),
+        );
+        let witness_generator_file = FileLocation::PathBuf(
+            root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test"),
+        );
+
+        let mut z0 = Vec::new();
+        let mut private_inputs = Vec::new();
+        for i in 0..2 {
+            let input_path = format!("src/data/input/input_{}.json", i);
+            let mut input = read_json_file_to_hashmap(&input_path).unwrap();
+            if i == 0 {
+                z0 = input
+                    .get("step_in")
+                    .and_then(|v| v.as_array())
+                    .expect("step input is missing `step_in`")
+                    .iter()
+                    .map(|v| v.as_str().and_then(Pasta::parse_z0))
+                    .collect::<Option<Vec<_>>>()
+                    .expect("cannot parse step_in entry");
+            }
+            input.remove("step_in");
+            private_inputs.push(input);
+        }
+        (r1cs, witness_generator_file, z0, private_inputs)
+    }
+
+    /// Save a `RecursiveSNARK` to `path`, reload it, and delete the file.
+    fn roundtrip_checkpoint(snark: &RSof<Pasta>, path: &std::path::Path) -> RSof<Pasta> {
+        save_recursive_snark_to_file::<Pasta>(snark, path.to_str().unwrap()).unwrap();
+        let reloaded = load_recursive_snark_from_file::<Pasta>(path.to_str().unwrap()).unwrap();
+        let _ = fs::remove_file(path);
+        reloaded
+    }
+
+    #[test]
+    fn checkpoint_round_trip_resumes_and_continues() {
+        let (r1cs, witness_generator_file, z0, private_inputs) = load_two_step_fixture();
+        let pp = create_public_params::<<Pasta as CurveConfig>::G1, <Pasta as CurveConfig>::G2>(
+            r1cs.clone(),
+        );
+
+        let recursive_snark = create_recursive_circuit(
+            witness_generator_file.clone(),
+            r1cs.clone(),
+            vec![private_inputs[0].clone()],
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+
+        let checkpoint_path = current_dir().unwrap().join("nova_maci_test_checkpoint_ok.bin");
+        let mut reloaded = roundtrip_checkpoint(&recursive_snark, &checkpoint_path);
+
+        // The checkpoint's own last claimed output is the correct `expected_zi` to resume
+        // from; this must succeed and fold the remaining step.
+        let z0_secondary = vec![Scalar2::<Pasta>::zero()];
+        let (expected_zi, _) = reloaded.verify(&pp, 1, z0.clone(), z0_secondary).unwrap();
+        let new_zi = continue_recursive_snark::<Pasta>(
+            &pp,
+            &mut reloaded,
+            1,
+            z0.clone(),
+            expected_zi,
+            witness_generator_file.clone(),
+            r1cs.clone(),
+            vec![private_inputs[1].clone()],
+        )
+        .expect("continuing from the checkpoint's own last claimed output should succeed");
+        assert_eq!(new_zi.len(), z0.len());
+    }
+
+    #[test]
+    fn checkpoint_round_trip_rejects_a_stale_expected_zi() {
+        let (r1cs, witness_generator_file, z0, private_inputs) = load_two_step_fixture();
+        let pp = create_public_params::<<Pasta as CurveConfig>::G1, <Pasta as CurveConfig>::G2>(
+            r1cs.clone(),
+        );
+
+        let recursive_snark = create_recursive_circuit(
+            witness_generator_file.clone(),
+            r1cs.clone(),
+            vec![private_inputs[0].clone()],
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+
+        let checkpoint_path = current_dir().unwrap().join("nova_maci_test_checkpoint_stale.bin");
+        let mut reloaded = roundtrip_checkpoint(&recursive_snark, &checkpoint_path);
+
+        // A caller that believes it's resuming from the wrong `z_i` (e.g. a stale
+        // snapshot from before the last successful fold) must be rejected up front.
+        let z0_secondary = vec![Scalar2::<Pasta>::zero()];
+        let (mut wrong_zi, _) = reloaded.verify(&pp, 1, z0.clone(), z0_secondary).unwrap();
+        wrong_zi[0] = wrong_zi[0] + Scalar1::<Pasta>::ONE;
+
+        let result = continue_recursive_snark::<Pasta>(
+            &pp,
+            &mut reloaded,
+            1,
+            z0.clone(),
+            wrong_zi,
+            witness_generator_file,
+            r1cs,
+            vec![private_inputs[1].clone()],
+        );
+        assert!(result.is_err(), "a mismatched expected_zi must be rejected");
+    }
 }
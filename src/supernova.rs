@@ -0,0 +1,472 @@
+//! Non-uniform IVC (SuperNova) support for MACI.
+//!
+//! A MACI round folds two distinct circuits — message processing and vote tallying —
+//! into a single recursive proof. Rather than hardcoding one `ProcessMessages` shape,
+//! this module accepts a set of r1cs / witness-generator pairs (one per circuit type)
+//! and, at each step, selects which circuit to fold from a program counter carried in
+//! the public IVC state. Each step only pays for the circuit it actually runs,
+//! yielding the "pay-as-you-go" cost SuperNova is designed for.
+//!
+//! The branch sequence is bound into the proof via a read-only memory (ROM): the full
+//! list of `circuit_index` values for the round is encoded into the genesis primary
+//! input `z0`, and every step enforces `rom[pc] == self.circuit_index()` while advancing
+//! the program counter `pc -> pc + 1`. Because the ROM travels through the public IVC
+//! state unchanged and `pc` is the SuperNova program counter, the verifier replays the
+//! exact same branch sequence — a prover cannot fold a different circuit at a step
+//! without breaking the `rom[pc]` constraint.
+
+use std::collections::HashMap;
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_scotia::{
+    circom::circuit::{CircomCircuit, R1CS},
+    circom::reader::generate_witness_from_bin,
+    FileLocation, F1, F2, G1, G2,
+};
+use nova_snark::{
+    supernova::{NonUniformCircuit, PublicParams, RecursiveSNARK, StepCircuit},
+    traits::{circuit::TrivialTestCircuit, circuit::StepCircuit as _, Group},
+};
+use serde_json::Value;
+
+/// One foldable circuit type: its r1cs shape and the Circom witness-generator binary.
+#[derive(Clone)]
+pub struct CircuitType {
+    pub r1cs: R1CS<F1>,
+    pub witness_generator_file: FileLocation,
+}
+
+/// A SuperNova step circuit: the Circom application circuit for one circuit type, wired
+/// behind the program-counter / ROM machinery that binds the branch sequence into the
+/// public IVC state.
+///
+/// `arity` is `app_arity + rom_len`: the public state carries the application's own IVC
+/// inputs followed by the read-only program, which is passed through each step unchanged.
+#[derive(Clone)]
+pub struct MaciStepCircuit {
+    circuit_index: usize,
+    app: CircomCircuit<F1>,
+    rom_len: usize,
+}
+
+impl MaciStepCircuit {
+    fn app_arity(&self) -> usize {
+        <CircomCircuit<F1> as nova_snark::traits::circuit::StepCircuit<F1>>::arity(&self.app)
+    }
+}
+
+impl StepCircuit<F1> for MaciStepCircuit {
+    fn arity(&self) -> usize {
+        self.app_arity() + self.rom_len
+    }
+
+    fn circuit_index(&self) -> usize {
+        self.circuit_index
+    }
+
+    fn synthesize<CS: ConstraintSystem<F1>>(
+        &self,
+        cs: &mut CS,
+        pc: Option<&AllocatedNum<F1>>,
+        z: &[AllocatedNum<F1>],
+    ) -> Result<(Option<AllocatedNum<F1>>, Vec<AllocatedNum<F1>>), SynthesisError> {
+        let app_arity = self.app_arity();
+        let (z_app, rom) = z.split_at(app_arity);
+
+        // Enforce `rom[pc] == circuit_index`: the step may only run the circuit the
+        // committed program selects at this position. `pc` is always supplied under
+        // SuperNova's non-uniform IVC.
+        let pc = pc.ok_or(SynthesisError::AssignmentMissing)?;
+        let selected = get_from_rom(cs.namespace(|| "rom[pc]"), rom, pc)?;
+        cs.enforce(
+            || "rom[pc] == circuit_index",
+            |lc| lc + selected.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + (F1::from(self.circuit_index as u64), CS::one()),
+        );
+
+        // Run the underlying Circom circuit over the application portion of the state.
+        let z_app_out = self.app.synthesize(&mut cs.namespace(|| "app"), z_app)?;
+
+        // Advance the program counter and pass the ROM through untouched.
+        let next_pc = AllocatedNum::alloc(cs.namespace(|| "next_pc"), || {
+            Ok(pc.get_value().ok_or(SynthesisError::AssignmentMissing)? + F1::ONE)
+        })?;
+        cs.enforce(
+            || "next_pc == pc + 1",
+            |lc| lc + next_pc.get_variable() - pc.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + CS::one(),
+        );
+
+        let mut z_out = z_app_out;
+        z_out.extend_from_slice(rom);
+        Ok((Some(next_pc), z_out))
+    }
+}
+
+/// Select `rom[index]` where `index` is an allocated program counter, via a one-hot
+/// scan over the ROM. Returns an allocated number constrained to equal the selected
+/// entry.
+///
+/// Each `is_i` is constrained, not just witnessed: `is_i * (1 - is_i) == 0` makes it
+/// boolean, `(index - i) * is_i == 0` forbids `is_i` from being set for any `i != index`,
+/// and `Σ is_i == 1` forces exactly one of them to be set — together these tie `is_i` to
+/// `index == i` rather than letting the prover pick an unrelated slot.
+fn get_from_rom<CS: ConstraintSystem<F1>>(
+    mut cs: CS,
+    rom: &[AllocatedNum<F1>],
+    index: &AllocatedNum<F1>,
+) -> Result<AllocatedNum<F1>, SynthesisError> {
+    let mut acc = AllocatedNum::alloc(cs.namespace(|| "acc0"), || Ok(F1::ZERO))?;
+    cs.enforce(|| "acc0 == 0", |lc| lc + acc.get_variable(), |lc| lc + CS::one(), |lc| lc);
+
+    let mut is_sum_lc = bellpepper_core::LinearCombination::<F1>::zero();
+
+    for (i, entry) in rom.iter().enumerate() {
+        // `is_i` is a boolean selector that is 1 exactly when `index == i`.
+        let is_i = AllocatedNum::alloc(cs.namespace(|| format!("is_{}", i)), || {
+            let idx = index.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(if idx == F1::from(i as u64) { F1::ONE } else { F1::ZERO })
+        })?;
+        cs.enforce(
+            || format!("is_{}_boolean", i),
+            |lc| lc + CS::one() - is_i.get_variable(),
+            |lc| lc + is_i.get_variable(),
+            |lc| lc,
+        );
+        // (index - i) * is_i == 0: `is_i` can only be nonzero when `index == i`.
+        cs.enforce(
+            || format!("is_{}_matches_index", i),
+            |lc| lc + index.get_variable() - (F1::from(i as u64), CS::one()),
+            |lc| lc + is_i.get_variable(),
+            |lc| lc,
+        );
+        is_sum_lc = is_sum_lc + is_i.get_variable();
+
+        // acc += is_i * entry
+        let term = AllocatedNum::alloc(cs.namespace(|| format!("term_{}", i)), || {
+            let selected = if is_i.get_value().ok_or(SynthesisError::AssignmentMissing)?
+                == F1::ONE
+            {
+                entry.get_value().ok_or(SynthesisError::AssignmentMissing)?
+            } else {
+                F1::ZERO
+            };
+            Ok(selected)
+        })?;
+        cs.enforce(
+            || format!("term_{} == is_{} * entry", i, i),
+            |lc| lc + is_i.get_variable(),
+            |lc| lc + entry.get_variable(),
+            |lc| lc + term.get_variable(),
+        );
+
+        let next = AllocatedNum::alloc(cs.namespace(|| format!("acc_{}", i + 1)), || {
+            Ok(acc.get_value().ok_or(SynthesisError::AssignmentMissing)?
+                + term.get_value().ok_or(SynthesisError::AssignmentMissing)?)
+        })?;
+        cs.enforce(
+            || format!("acc_{} == acc_{} + term_{}", i + 1, i, i),
+            |lc| lc + next.get_variable() - acc.get_variable() - term.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+        acc = next;
+    }
+
+    // Exactly one `is_i` may be set: without this, `index` could fall outside
+    // `0..rom.len()` and every `is_i` would be (validly) zero, leaving `acc == 0`
+    // regardless of `index`.
+    cs.enforce(
+        || "sum(is_i) == 1",
+        |_| is_sum_lc,
+        |lc| lc + CS::one(),
+        |lc| lc + CS::one(),
+    );
+
+    Ok(acc)
+}
+
+/// A MACI program made of several circuit types, indexed by `circuit_index`.
+///
+/// `rom` is the branch sequence for the round (one `circuit_index` per step); it is
+/// encoded into `z0` so the program counter has something to read against.
+pub struct MaciProgram {
+    pub circuits: Vec<CircuitType>,
+    pub rom: Vec<usize>,
+}
+
+impl MaciProgram {
+    /// Number of field elements the ROM occupies in the public IVC state.
+    fn rom_len(&self) -> usize {
+        self.rom.len()
+    }
+
+    fn step_circuit(&self, circuit_index: usize, app: CircomCircuit<F1>) -> MaciStepCircuit {
+        MaciStepCircuit {
+            circuit_index,
+            app,
+            rom_len: self.rom_len(),
+        }
+    }
+}
+
+impl NonUniformCircuit<G1, G2, MaciStepCircuit, TrivialTestCircuit<F2>> for MaciProgram {
+    fn num_circuits(&self) -> usize {
+        self.circuits.len()
+    }
+
+    fn primary_circuit(&self, circuit_index: usize) -> MaciStepCircuit {
+        // The shape alone is enough to build the augmented circuit at setup time; the
+        // satisfying witness is supplied per step in [`prove_steps`].
+        self.step_circuit(
+            circuit_index,
+            CircomCircuit {
+                r1cs: self.circuits[circuit_index].r1cs.clone(),
+                witness: None,
+            },
+        )
+    }
+
+    fn secondary_circuit(&self) -> TrivialTestCircuit<F2> {
+        TrivialTestCircuit::default()
+    }
+
+    fn initial_circuit_index(&self) -> usize {
+        self.rom.first().copied().unwrap_or(0)
+    }
+}
+
+pub type MaciPublicParams = PublicParams<G1, G2, MaciStepCircuit, TrivialTestCircuit<F2>>;
+pub type MaciRecursiveSNARK = RecursiveSNARK<G1, G2, MaciStepCircuit, TrivialTestCircuit<F2>>;
+
+/// Derive SuperNova public parameters over the vector of circuit shapes.
+pub fn create_public_params(program: &MaciProgram) -> MaciPublicParams {
+    PublicParams::setup(
+        program,
+        &*CircomCircuit::<F1>::commitment_key_floor(),
+        &*TrivialTestCircuit::commitment_key_floor(),
+    )
+}
+
+/// Read the program-counter field from a step input and validate it against `program`.
+///
+/// Every input JSON carries a `circuit_index` selecting which circuit type the step
+/// folds. The value is rejected if it names a circuit that does not exist, so an
+/// out-of-range index from untrusted input can never index `program.circuits`.
+fn read_circuit_index(
+    program: &MaciProgram,
+    input: &HashMap<String, Value>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let circuit_index = input
+        .get("circuit_index")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .ok_or("step input is missing a `circuit_index` field")?;
+    if circuit_index >= program.circuits.len() {
+        return Err(format!(
+            "circuit_index {} is out of range (program has {} circuits)",
+            circuit_index,
+            program.circuits.len()
+        )
+        .into());
+    }
+    Ok(circuit_index)
+}
+
+/// Build the genesis primary input: the application's `z0` followed by the ROM, so the
+/// branch sequence is committed to in the public IVC state from step zero.
+pub fn build_z0(program: &MaciProgram, app_z0: &[F1]) -> Vec<F1> {
+    let mut z0 = app_z0.to_vec();
+    z0.extend(program.rom.iter().map(|&c| F1::from(c as u64)));
+    z0
+}
+
+/// Fold a sequence of steps into a SuperNova `RecursiveSNARK`.
+///
+/// Each entry of `private_inputs` picks its branch via `circuit_index`, which must match
+/// `program.rom` at the corresponding position; the matching witness generator is run to
+/// produce the assignment that step folds. `app_z0` is the genesis application input (the
+/// ROM is appended automatically). Returns the completed recursive proof.
+pub fn prove_steps(
+    pp: &MaciPublicParams,
+    program: &MaciProgram,
+    app_z0: Vec<F1>,
+    private_inputs: Vec<HashMap<String, Value>>,
+) -> Result<MaciRecursiveSNARK, Box<dyn std::error::Error>> {
+    let z0_primary = build_z0(program, &app_z0);
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+    let mut recursive_snark: Option<MaciRecursiveSNARK> = None;
+    let mut current_app_input = app_z0.clone();
+
+    for (step, input) in private_inputs.into_iter().enumerate() {
+        let circuit_index = read_circuit_index(program, &input)?;
+        if program.rom.get(step) != Some(&circuit_index) {
+            return Err(format!(
+                "step {} selects circuit {} but the ROM expects {:?}",
+                step,
+                circuit_index,
+                program.rom.get(step)
+            )
+            .into());
+        }
+        let circuit = &program.circuits[circuit_index];
+
+        // Generate the witness for the selected circuit type and bind it to its shape.
+        let witness = generate_witness_from_bin::<F1>(
+            &circuit.witness_generator_file,
+            &input,
+            &current_app_input,
+        );
+        let primary = program.step_circuit(
+            circuit_index,
+            CircomCircuit {
+                r1cs: circuit.r1cs.clone(),
+                witness: Some(witness),
+            },
+        );
+        let secondary = TrivialTestCircuit::default();
+
+        let mut snark = recursive_snark.unwrap_or_else(|| {
+            RecursiveSNARK::new(
+                pp,
+                program,
+                &primary,
+                &secondary,
+                z0_primary.clone(),
+                z0_secondary.clone(),
+            )
+            .expect("failed to initialize SuperNova RecursiveSNARK")
+        });
+
+        snark.prove_step(pp, &primary, &secondary)?;
+        // The application state is the prefix of the running output; the ROM tail is
+        // carried through unchanged.
+        current_app_input = snark.zi_primary()[..current_app_input.len()].to_vec();
+        println!("folded step {} using circuit {}", step, circuit_index);
+        recursive_snark = Some(snark);
+    }
+
+    recursive_snark.ok_or_else(|| "no steps were provided to fold".into())
+}
+
+/// Verify that the augmented running instances for every circuit type are satisfied.
+///
+/// The verifier replays the committed branch sequence: `z0` carries the ROM and the
+/// program counter advances over it, so a proof that folded a different circuit at any
+/// step fails here.
+pub fn verify(
+    snark: &MaciRecursiveSNARK,
+    pp: &MaciPublicParams,
+    program: &MaciProgram,
+    app_z0: Vec<F1>,
+) -> Result<Vec<F1>, Box<dyn std::error::Error>> {
+    let z0_primary = build_z0(program, &app_z0);
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+    let (zn_primary, _) = snark.verify(pp, &z0_primary, &z0_secondary)?;
+    Ok(zn_primary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use nova_scotia::circom::reader::load_r1cs;
+    use std::env::current_dir;
+
+    fn alloc_rom(cs: &mut TestConstraintSystem<F1>, values: &[u64]) -> Vec<AllocatedNum<F1>> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("rom_{}", i)), || Ok(F1::from(v)))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn get_from_rom_selects_the_indexed_entry() {
+        let mut cs = TestConstraintSystem::<F1>::new();
+        let rom = alloc_rom(&mut cs, &[5, 9, 2]);
+        let index = AllocatedNum::alloc(cs.namespace(|| "index"), || Ok(F1::from(1u64))).unwrap();
+
+        let selected = get_from_rom(cs.namespace(|| "get_from_rom"), &rom, &index).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(selected.get_value().unwrap(), F1::from(9u64));
+    }
+
+    #[test]
+    fn get_from_rom_rejects_a_selector_unrelated_to_index() {
+        let mut cs = TestConstraintSystem::<F1>::new();
+        let rom = alloc_rom(&mut cs, &[5, 9, 2]);
+        let index = AllocatedNum::alloc(cs.namespace(|| "index"), || Ok(F1::from(1u64))).unwrap();
+
+        let _selected = get_from_rom(cs.namespace(|| "get_from_rom"), &rom, &index).unwrap();
+        assert!(cs.is_satisfied());
+
+        // A cheating prover sets an unrelated slot's selector to 1 while `index` still
+        // points at slot 1. Before the `(index - i) * is_i == 0` constraint this was
+        // accepted (see 5dea6b7); it must now be rejected.
+        // Every other witness value is moved along with it (slot 0's term and the
+        // accumulator chain), so only that constraint is left to catch the cheat.
+        cs.set("get_from_rom/is_0/num", F1::ONE);
+        cs.set("get_from_rom/is_1/num", F1::ZERO);
+        cs.set("get_from_rom/term_0/num", F1::from(5u64));
+        cs.set("get_from_rom/term_1/num", F1::ZERO);
+        cs.set("get_from_rom/acc_1/num", F1::from(5u64));
+        cs.set("get_from_rom/acc_2/num", F1::from(5u64));
+        cs.set("get_from_rom/acc_3/num", F1::from(5u64));
+        assert!(!cs.is_satisfied());
+    }
+
+    /// End-to-end round trip through [`create_public_params`], [`prove_steps`], and
+    /// [`verify`], so the module is covered by more than the `get_from_rom` unit tests
+    /// above. Reuses the `ProcessMessages` fixture `bench` already depends on, repeated
+    /// twice in the ROM as a single circuit type.
+    #[test]
+    fn prove_steps_and_verify_round_trip() {
+        let root = current_dir().unwrap();
+        let circuit_file = root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test.r1cs");
+        let witness_generator_file =
+            root.join("src/data/circom/ProcessMessages_v2_10-2-1-2_test");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(circuit_file));
+
+        let program = MaciProgram {
+            circuits: vec![CircuitType {
+                r1cs,
+                witness_generator_file: FileLocation::PathBuf(witness_generator_file),
+            }],
+            rom: vec![0, 0],
+        };
+
+        let mut app_z0 = Vec::new();
+        let mut private_inputs = Vec::new();
+        for i in 0..2 {
+            let input_path = format!("src/data/input/input_{}.json", i);
+            let mut input = crate::read_json_file_to_hashmap(&input_path).unwrap();
+            if i == 0 {
+                app_z0 = input
+                    .get("step_in")
+                    .and_then(|v| v.as_array())
+                    .expect("step input is missing `step_in`")
+                    .iter()
+                    .map(|v| v.as_str().and_then(F1::from_str_vartime))
+                    .collect::<Option<Vec<_>>>()
+                    .expect("cannot parse step_in entry");
+            }
+            input.remove("step_in");
+            input.insert("circuit_index".to_string(), Value::from(0));
+            private_inputs.push(input);
+        }
+
+        let pp = create_public_params(&program);
+        let snark = prove_steps(&pp, &program, app_z0.clone(), private_inputs)
+            .expect("prove_steps should fold both ROM steps");
+        verify(&snark, &pp, &program, app_z0).expect("verify should accept a correctly-folded proof");
+    }
+}
@@ -0,0 +1,135 @@
+//! Deterministic test harness for the folding machinery.
+//!
+//! The committed `input_*.json` fixtures exercise the full Circom witness pipeline,
+//! which makes it awkward to regression-test the nova-scotia / nova-snark integration
+//! in isolation. This module samples a satisfying relaxed-R1CS instance/witness pair
+//! directly from an `R1CSShape` and a commitment key, so folding can be tested without
+//! regenerating any Circom witnesses.
+
+use ff::Field;
+use nova_snark::{
+    r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
+    traits::{commitment::CommitmentEngineTrait, Group},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+type CE<G> = <G as Group>::CE;
+
+/// Sample a satisfying relaxed-R1CS instance/witness pair for `shape`.
+///
+/// Builds the full assignment `Z = [W, u, X]` with random `W`, random public `X`, and
+/// a random scalar `u`, multiplies it through the shape's sparse matrices to obtain
+/// `(AZ, BZ, CZ)`, sets the error vector `E = AZ ∘ BZ − u·CZ`, and commits to `W` and
+/// `E`. The resulting `(instance, witness)` satisfies the relaxed-R1CS relation by
+/// construction.
+pub fn sample_random_instance_witness<G: Group>(
+    shape: &R1CSShape<G>,
+    ck: &<CE<G> as CommitmentEngineTrait<G>>::CommitmentKey,
+    rng: &mut impl Rng,
+) -> (RelaxedR1CSInstance<G>, RelaxedR1CSWitness<G>) {
+    let w: Vec<G::Scalar> = (0..shape.num_vars).map(|_| G::Scalar::random(&mut *rng)).collect();
+    let x: Vec<G::Scalar> = (0..shape.num_io).map(|_| G::Scalar::random(&mut *rng)).collect();
+    let u = G::Scalar::random(&mut *rng);
+
+    // Z = [W, u, X]
+    let mut z = w.clone();
+    z.push(u);
+    z.extend_from_slice(&x);
+
+    let (az, bz, cz) = shape.multiply_vec(&z).expect("well-formed assignment");
+
+    // E = AZ ∘ BZ − u·CZ
+    let e: Vec<G::Scalar> = az
+        .iter()
+        .zip(bz.iter())
+        .zip(cz.iter())
+        .map(|((a, b), c)| *a * *b - u * *c)
+        .collect();
+
+    let comm_w = CE::<G>::commit(ck, &w);
+    let comm_e = CE::<G>::commit(ck, &e);
+
+    let witness = RelaxedR1CSWitness { W: w, E: e };
+    let instance = RelaxedR1CSInstance {
+        comm_W: comm_w,
+        comm_E: comm_e,
+        X: x,
+        u,
+    };
+
+    (instance, witness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::current_dir;
+
+    use nova_scotia::{
+        circom::reader::load_r1cs, circom::circuit::R1CS as CircomR1CS, FileLocation, G1, G2,
+    };
+    use nova_snark::r1cs::R1CS;
+
+    type G = G1;
+    type Fr = <G as Group>::Scalar;
+
+    const PROCESS_MESSAGES_R1CS: &str =
+        "src/data/circom/ProcessMessages_v2_10-2-1-2_test.r1cs";
+
+    // KNOWN GAP, TRACK AS FOLLOW-UP: this only borrows num_cons/num_vars/num_io from the
+    // real ProcessMessages fixture; the constraint matrices below are still a fabricated
+    // single constraint, not the fixture's actual A/B/C. That means this test catches a
+    // regression in the fixture's *dimensions* but not in how nova-scotia's real
+    // ProcessMessages constraints fold. Porting the true matrices needs the `ShapeCS`
+    // machinery nova-scotia builds internally when synthesizing a `CircomCircuit`,
+    // which isn't exposed for direct reuse here — raise that as separate follow-up
+    // work rather than reading this as full coverage of the real circuit's folding.
+    fn process_messages_shape() -> R1CSShape<G> {
+        let path = current_dir().unwrap().join(PROCESS_MESSAGES_R1CS);
+        let loaded: CircomR1CS<Fr> = load_r1cs::<G1, G2>(&FileLocation::PathBuf(path));
+
+        let num_cons = loaded.num_constraints;
+        let num_vars = loaded.num_variables;
+        let num_io = loaded.num_inputs;
+        let u_index = num_vars;
+        let a = vec![(0, 0, Fr::ONE)];
+        let b = vec![(0, 1, Fr::ONE)];
+        // A·Z ∘ B·Z must equal C·Z on the satisfying path; the sampler relaxes this
+        // with the error vector, so any C is acceptable here. Every row past 0 is left
+        // as the trivially-satisfied zero constraint.
+        let c = vec![(0, u_index, Fr::ONE)];
+        R1CSShape::new(num_cons, num_vars, num_io, &a, &b, &c).unwrap()
+    }
+
+    #[test]
+    fn sampled_pair_is_satisfied() {
+        let shape = process_messages_shape();
+        let ck = R1CS::<G>::commitment_key(&shape);
+        let mut rng = StdRng::seed_from_u64(0x4d414349); // "MACI"
+
+        let (u, w) = sample_random_instance_witness(&shape, &ck, &mut rng);
+        shape
+            .is_sat_relaxed(&ck, &u, &w)
+            .expect("sampled relaxed instance/witness should satisfy the shape");
+    }
+
+    #[test]
+    fn folding_two_sampled_pairs_is_satisfied() {
+        let shape = process_messages_shape();
+        let ck = R1CS::<G>::commitment_key(&shape);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let (u1, w1) = sample_random_instance_witness(&shape, &ck, &mut rng);
+        let (u2, w2) = sample_random_instance_witness(&shape, &ck, &mut rng);
+
+        let (t, comm_t) = shape.commit_T(&ck, &u1, &w1, &u2, &w2).unwrap();
+        let r = Fr::random(&mut rng);
+
+        let folded_u = u1.fold(&u2, &comm_t, &r);
+        let folded_w = w1.fold(&w2, &t, &r).unwrap();
+
+        shape
+            .is_sat_relaxed(&ck, &folded_u, &folded_w)
+            .expect("folding two satisfied instances should yield a satisfied instance");
+    }
+}